@@ -0,0 +1,291 @@
+use crate::auth::AuthenticationError;
+use base64::Engine;
+use serde::Deserialize;
+
+/// A third-party identity provider we can authenticate a user through.
+///
+/// Each variant knows its own authorization endpoint, token endpoint, and
+/// how to normalize that provider's profile response into the fields we
+/// actually care about (`id` and, where available, `email`).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AuthProvider {
+    GitHub,
+    Discord,
+    Google,
+    Microsoft,
+    Apple,
+    GitLab,
+}
+
+/// A provider profile, normalized to the handful of fields every flow
+/// needs in order to look up or create a `User`.
+pub struct AuthUser {
+    pub id: String,
+    pub email: Option<String>,
+}
+
+/// Both halves of a token exchange response: the bearer access token used
+/// against most providers' REST profile endpoints, and, where the
+/// provider issues one, the OIDC `id_token`. Apple has no REST profile
+/// endpoint at all — its `id_token` is the only place identity lives.
+pub struct ProviderToken {
+    pub access_token: String,
+    pub id_token: Option<String>,
+}
+
+impl AuthProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthProvider::GitHub => "github",
+            AuthProvider::Discord => "discord",
+            AuthProvider::Google => "google",
+            AuthProvider::Microsoft => "microsoft",
+            AuthProvider::Apple => "apple",
+            AuthProvider::GitLab => "gitlab",
+        }
+    }
+
+    /// Token prefixes this provider's own access tokens are recognized by
+    /// when presented directly as a bearer token (as opposed to the code
+    /// we exchange for one during the login flow).
+    pub fn token_prefixes(&self) -> &'static [&'static str] {
+        match self {
+            AuthProvider::GitHub => &["github", "gho", "ghp"],
+            AuthProvider::Discord => &["discord"],
+            AuthProvider::Google => &["google"],
+            AuthProvider::Microsoft => &["microsoft"],
+            AuthProvider::Apple => &["apple"],
+            AuthProvider::GitLab => &["gitlab"],
+        }
+    }
+
+    pub fn authorize_url(&self, state: &str) -> Result<String, AuthenticationError> {
+        let (base, client_id, scope) = match self {
+            AuthProvider::GitHub => (
+                "https://github.com/login/oauth/authorize",
+                env_var("GITHUB_CLIENT_ID")?,
+                "read:user user:email",
+            ),
+            AuthProvider::Discord => (
+                "https://discord.com/api/oauth2/authorize",
+                env_var("DISCORD_CLIENT_ID")?,
+                "identify email",
+            ),
+            AuthProvider::Google => (
+                "https://accounts.google.com/o/oauth2/v2/auth",
+                env_var("GOOGLE_CLIENT_ID")?,
+                "openid email profile",
+            ),
+            AuthProvider::Microsoft => (
+                "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+                env_var("MICROSOFT_CLIENT_ID")?,
+                "openid email profile",
+            ),
+            AuthProvider::Apple => (
+                "https://appleid.apple.com/auth/authorize",
+                env_var("APPLE_CLIENT_ID")?,
+                "name email",
+            ),
+            AuthProvider::GitLab => (
+                "https://gitlab.com/oauth/authorize",
+                env_var("GITLAB_CLIENT_ID")?,
+                "read_user",
+            ),
+        };
+
+        let redirect_uri = format!(
+            "{}/v3/auth/callback/{}",
+            env_var("SELF_ADDR")?,
+            self.as_str()
+        );
+
+        Ok(format!(
+            "{base}?client_id={client_id}&redirect_uri={redirect_uri}&scope={scope}&state={state}&response_type=code"
+        ))
+    }
+
+    fn token_url(&self) -> &'static str {
+        match self {
+            AuthProvider::GitHub => "https://github.com/login/oauth/access_token",
+            AuthProvider::Discord => "https://discord.com/api/oauth2/token",
+            AuthProvider::Google => "https://oauth2.googleapis.com/token",
+            AuthProvider::Microsoft => "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+            AuthProvider::Apple => "https://appleid.apple.com/auth/token",
+            AuthProvider::GitLab => "https://gitlab.com/oauth/token",
+        }
+    }
+
+    fn profile_url(&self) -> &'static str {
+        match self {
+            AuthProvider::GitHub => "https://api.github.com/user",
+            AuthProvider::Discord => "https://discord.com/api/users/@me",
+            AuthProvider::Google => "https://www.googleapis.com/oauth2/v3/userinfo",
+            AuthProvider::Microsoft => "https://graph.microsoft.com/v1.0/me",
+            // Apple has no REST profile endpoint; `get_user` reads the
+            // `id_token` instead and never reaches this arm.
+            AuthProvider::Apple => unreachable!("Apple identity comes from id_token, not profile_url"),
+            AuthProvider::GitLab => "https://gitlab.com/api/v4/user",
+        }
+    }
+
+    /// Exchanges an authorization `code` obtained from this provider's
+    /// consent screen for a token we can identify the user with.
+    pub async fn get_token(&self, code: &str) -> Result<ProviderToken, AuthenticationError> {
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            id_token: Option<String>,
+        }
+
+        let client = reqwest::Client::new();
+        let redirect_uri = format!(
+            "{}/v3/auth/callback/{}",
+            env_var("SELF_ADDR")?,
+            self.as_str()
+        );
+
+        let res: TokenResponse = client
+            .post(self.token_url())
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[
+                ("client_id", env_var(&format!("{}_CLIENT_ID", self.as_str().to_uppercase()))?),
+                (
+                    "client_secret",
+                    env_var(&format!("{}_CLIENT_SECRET", self.as_str().to_uppercase()))?,
+                ),
+                ("code", code.to_string()),
+                ("grant_type", "authorization_code".to_string()),
+                ("redirect_uri", redirect_uri),
+            ])
+            .send()
+            .await
+            .map_err(|_| AuthenticationError::InvalidCredentials)?
+            .json()
+            .await
+            .map_err(|_| AuthenticationError::InvalidCredentials)?;
+
+        Ok(ProviderToken {
+            access_token: res.access_token,
+            id_token: res.id_token,
+        })
+    }
+
+    /// Fetches and normalizes the profile belonging to a provider token
+    /// obtained either via `get_token` or presented directly (in which
+    /// case `id_token` is never set, since a raw access token carries no
+    /// JWT alongside it).
+    ///
+    /// Apple has no REST profile endpoint at all: its token response's
+    /// `id_token` is the only place a user's identity appears, so that
+    /// arm decodes the JWT payload directly instead of calling out to
+    /// `profile_url()`. The signature isn't verified here since Apple's
+    /// public keys change over time and we only trust `id_token` values
+    /// that came from our own server-to-server exchange with `code` in
+    /// `get_token` above, never one supplied directly by a client.
+    pub async fn get_user(&self, token: &ProviderToken) -> Result<AuthUser, AuthenticationError> {
+        if *self == AuthProvider::Apple {
+            let id_token = token
+                .id_token
+                .as_deref()
+                .ok_or(AuthenticationError::InvalidCredentials)?;
+            return Self::decode_apple_id_token(id_token);
+        }
+
+        let client = reqwest::Client::new();
+        let value: serde_json::Value = client
+            .get(self.profile_url())
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {}", token.access_token),
+            )
+            .header(reqwest::header::USER_AGENT, "Modrinth")
+            .send()
+            .await
+            .map_err(|_| AuthenticationError::InvalidCredentials)?
+            .json()
+            .await
+            .map_err(|_| AuthenticationError::InvalidCredentials)?;
+
+        let (id_field, email_field) = match self {
+            AuthProvider::GitHub => ("id", "email"),
+            AuthProvider::Discord => ("id", "email"),
+            AuthProvider::Google => ("sub", "email"),
+            AuthProvider::Microsoft => ("id", "mail"),
+            AuthProvider::Apple => unreachable!("handled above"),
+            AuthProvider::GitLab => ("id", "email"),
+        };
+
+        let id = value
+            .get(id_field)
+            .and_then(|x| x.as_str().map(String::from).or_else(|| x.as_i64().map(|x| x.to_string())))
+            .ok_or(AuthenticationError::InvalidCredentials)?;
+
+        let email = value
+            .get(email_field)
+            .and_then(|x| x.as_str())
+            .map(String::from);
+
+        Ok(AuthUser { id, email })
+    }
+
+    fn decode_apple_id_token(id_token: &str) -> Result<AuthUser, AuthenticationError> {
+        let payload = id_token
+            .split('.')
+            .nth(1)
+            .ok_or(AuthenticationError::InvalidCredentials)?;
+
+        let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(payload)
+            .map_err(|_| AuthenticationError::InvalidCredentials)?;
+
+        let claims: serde_json::Value =
+            serde_json::from_slice(&decoded).map_err(|_| AuthenticationError::InvalidCredentials)?;
+
+        let id = claims
+            .get("sub")
+            .and_then(|x| x.as_str())
+            .map(String::from)
+            .ok_or(AuthenticationError::InvalidCredentials)?;
+
+        let email = claims
+            .get("email")
+            .and_then(|x| x.as_str())
+            .map(String::from);
+
+        Ok(AuthUser { id, email })
+    }
+
+    /// Looks up the `UserId` previously linked to this provider's account
+    /// `id`, if any account has been linked to it yet.
+    pub async fn get_user_id<'a, E>(
+        &self,
+        id: &str,
+        executor: E,
+    ) -> Result<Option<crate::database::models::ids::UserId>, AuthenticationError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let result = sqlx::query!(
+            "
+            SELECT id FROM users
+            WHERE github_id::text = $1 AND $2 = 'github'
+               OR discord_id::text = $1 AND $2 = 'discord'
+               OR google_id = $1 AND $2 = 'google'
+               OR microsoft_id = $1 AND $2 = 'microsoft'
+               OR apple_id = $1 AND $2 = 'apple'
+               OR gitlab_id::text = $1 AND $2 = 'gitlab'
+            ",
+            id,
+            self.as_str()
+        )
+        .fetch_optional(executor)
+        .await
+        .map_err(|_| AuthenticationError::InvalidCredentials)?;
+
+        Ok(result.map(|r| crate::database::models::ids::UserId(r.id)))
+    }
+}
+
+fn env_var(key: &str) -> Result<String, AuthenticationError> {
+    dotenvy::var(key).map_err(|_| AuthenticationError::InvalidAuthMethod)
+}
@@ -1,8 +1,11 @@
 use crate::auth::flows::AuthProvider;
 use crate::auth::session::get_session_metadata;
 use crate::auth::AuthenticationError;
+use crate::database::models::pat_item;
 use crate::database::models::user_item;
+use crate::models::pats::Scopes;
 use crate::models::users::{Role, User, UserId, UserPayoutData};
+use crate::queue::pat::PatQueue;
 use crate::queue::session::SessionQueue;
 use actix_web::HttpRequest;
 use chrono::Utc;
@@ -13,7 +16,8 @@ pub async fn get_user_from_headers<'a, E>(
     executor: E,
     redis: &deadpool_redis::Pool,
     session_queue: &SessionQueue,
-) -> Result<User, AuthenticationError>
+    pat_queue: &PatQueue,
+) -> Result<(User, Scopes), AuthenticationError>
 where
     E: sqlx::Executor<'a, Database = sqlx::Postgres> + Copy,
 {
@@ -21,7 +25,7 @@ where
     let token: Option<&HeaderValue> = headers.get(AUTHORIZATION);
 
     // Fetch DB user record and minos user from headers
-    let db_user = get_user_record_from_bearer_token(
+    let (db_user, scopes) = get_user_record_from_bearer_token(
         req,
         token
             .ok_or_else(|| AuthenticationError::InvalidAuthMethod)?
@@ -30,6 +34,7 @@ where
         executor,
         redis,
         session_queue,
+        pat_queue,
     )
     .await?
     .ok_or_else(|| AuthenticationError::InvalidCredentials)?;
@@ -37,11 +42,11 @@ where
     let user = User {
         id: UserId::from(db_user.id),
         github_id: db_user.github_id.map(|x| x as u64),
-        // discord_id: minos_user.discord_id,
-        // google_id: minos_user.google_id,
-        // microsoft_id: minos_user.microsoft_id,
-        // apple_id: minos_user.apple_id,
-        // gitlab_id: minos_user.gitlab_id,
+        discord_id: db_user.discord_id.map(|x| x as u64),
+        google_id: db_user.google_id,
+        microsoft_id: db_user.microsoft_id,
+        apple_id: db_user.apple_id,
+        gitlab_id: db_user.gitlab_id.map(|x| x as u64),
         username: db_user.username,
         name: db_user.name,
         email: db_user.email,
@@ -49,7 +54,7 @@ where
         bio: db_user.bio,
         created: db_user.created,
         role: Role::from_string(&db_user.role),
-        badges: db_user.badges,
+        badges: db_user.badges as u64,
         payout_data: Some(UserPayoutData {
             balance: db_user.balance,
             payout_wallet: db_user.payout_wallet,
@@ -57,7 +62,7 @@ where
             payout_address: db_user.payout_address,
         }),
     };
-    Ok(user)
+    Ok((user, scopes))
 }
 
 pub async fn get_user_record_from_bearer_token<'a, 'b, E>(
@@ -66,12 +71,27 @@ pub async fn get_user_record_from_bearer_token<'a, 'b, E>(
     executor: E,
     redis: &deadpool_redis::Pool,
     session_queue: &SessionQueue,
-) -> Result<Option<user_item::User>, AuthenticationError>
+    pat_queue: &PatQueue,
+) -> Result<Option<(user_item::User, Scopes)>, AuthenticationError>
 where
     E: sqlx::Executor<'a, Database = sqlx::Postgres> + Copy,
 {
     let possible_user = match token.split_once('_') {
-        //Some(("modrinth", _)) => get_user_from_pat(token, executor).await?,
+        Some(("mrp", _)) => {
+            let pat = pat_item::PersonalAccessToken::get_by_token(token, executor)
+                .await?
+                .ok_or_else(|| AuthenticationError::InvalidCredentials)?;
+
+            if pat.expires.map(|expires| expires < Utc::now()).unwrap_or(false) {
+                return Err(AuthenticationError::InvalidCredentials);
+            }
+
+            let user = user_item::User::get_id(pat.user_id, executor).await?;
+
+            pat_queue.add(pat.id).await;
+
+            user.map(|user| (user, pat.scopes))
+        }
         Some(("mra", _)) => {
             let session =
                 crate::database::models::session_item::Session::get(token, executor, redis)
@@ -82,39 +102,78 @@ where
                 return Err(AuthenticationError::InvalidCredentials);
             }
 
-            let user = user_item::User::get_id(session.user_id, executor, redis).await?;
+            let user = user_item::User::get_id(session.user_id, executor).await?;
 
             let metadata = get_session_metadata(req).await?;
             session_queue.add(session.id, metadata).await;
 
-            user
+            user.map(|user| (user, Scopes::all_scopes()))
         }
         Some(("github", _)) | Some(("gho", _)) | Some(("ghp", _)) => {
-            let user = AuthProvider::GitHub.get_user(token).await?;
+            let user = AuthProvider::GitHub
+                .get_user(&crate::auth::flows::ProviderToken {
+                    access_token: token.to_string(),
+                    id_token: None,
+                })
+                .await?;
             let id = AuthProvider::GitHub.get_user_id(&user.id, executor).await?;
 
             user_item::User::get_id(
                 id.ok_or_else(|| AuthenticationError::InvalidCredentials)?,
                 executor,
-                redis,
             )
             .await?
+            .map(|user| (user, Scopes::all_scopes()))
+        }
+        Some(("discord", _)) => get_user_via_provider(AuthProvider::Discord, token, executor).await?,
+        Some(("google", _)) => get_user_via_provider(AuthProvider::Google, token, executor).await?,
+        Some(("microsoft", _)) => {
+            get_user_via_provider(AuthProvider::Microsoft, token, executor).await?
         }
+        Some(("apple", _)) => get_user_via_provider(AuthProvider::Apple, token, executor).await?,
+        Some(("gitlab", _)) => get_user_via_provider(AuthProvider::GitLab, token, executor).await?,
         _ => return Err(AuthenticationError::InvalidAuthMethod),
     };
     Ok(possible_user)
 }
 
+async fn get_user_via_provider<'a, E>(
+    provider: AuthProvider,
+    token: &str,
+    executor: E,
+) -> Result<Option<(user_item::User, Scopes)>, AuthenticationError>
+where
+    E: sqlx::Executor<'a, Database = sqlx::Postgres> + Copy,
+{
+    let auth_user = provider
+        .get_user(&crate::auth::flows::ProviderToken {
+            access_token: token.to_string(),
+            id_token: None,
+        })
+        .await?;
+    let id = provider.get_user_id(&auth_user.id, executor).await?;
+
+    Ok(
+        user_item::User::get_id(
+            id.ok_or_else(|| AuthenticationError::InvalidCredentials)?,
+            executor,
+        )
+        .await?
+        .map(|user| (user, Scopes::all_scopes())),
+    )
+}
+
 pub async fn check_is_moderator_from_headers<'a, 'b, E>(
     req: &HttpRequest,
     executor: E,
     redis: &deadpool_redis::Pool,
     session_queue: &SessionQueue,
+    pat_queue: &PatQueue,
 ) -> Result<User, AuthenticationError>
 where
     E: sqlx::Executor<'a, Database = sqlx::Postgres> + Copy,
 {
-    let user = get_user_from_headers(req, executor, redis, session_queue).await?;
+    let (user, _scopes) = get_user_from_headers(req, executor, redis, session_queue, pat_queue).await?;
 
     if user.role.is_mod() {
         Ok(user)
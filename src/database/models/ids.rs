@@ -0,0 +1,36 @@
+use crate::database::models::DatabaseError;
+use rand::Rng;
+
+/// Raw, DB-layer identifiers. These wrap the bare `bigint` primary keys
+/// used in Postgres; the Base62-encoded, API-facing counterparts living
+/// in `crate::models::ids` convert to/from these.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct UserId(pub i64);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct PatId(pub i64);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct OAuthClientId(pub i64);
+
+/// Picks a random 63-bit id, retrying against the `pats` table until it
+/// finds one that isn't already taken.
+pub async fn generate_pat_id<'a, E>(exec: E) -> Result<PatId, DatabaseError>
+where
+    E: sqlx::Executor<'a, Database = sqlx::Postgres> + Copy,
+{
+    loop {
+        let id = PatId(rand::thread_rng().gen_range(1..i64::MAX));
+
+        let existing = sqlx::query!(
+            "SELECT EXISTS(SELECT 1 FROM pats WHERE id = $1) AS exists",
+            id.0
+        )
+        .fetch_one(exec)
+        .await?;
+
+        if !existing.exists.unwrap_or(false) {
+            return Ok(id);
+        }
+    }
+}
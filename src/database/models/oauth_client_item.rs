@@ -0,0 +1,51 @@
+use super::ids::{OAuthClientId, UserId};
+use crate::database::models::DatabaseError;
+use crate::models::pats::Scopes;
+use chrono::{DateTime, Utc};
+
+pub struct OAuthClient {
+    pub id: OAuthClientId,
+    pub client_id: String,
+    pub client_secret_hash: Option<String>,
+    pub name: String,
+    pub owner_id: UserId,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Scopes,
+    pub created: DateTime<Utc>,
+}
+
+impl OAuthClient {
+    pub async fn get_by_client_id<'a, E>(
+        client_id: &str,
+        exec: E,
+    ) -> Result<Option<OAuthClient>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let row = sqlx::query!(
+            "
+            SELECT id, client_id, client_secret_hash, name, owner_id, redirect_uris, allowed_scopes, created
+            FROM oauth_clients
+            WHERE client_id = $1
+            ",
+            client_id
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|r| OAuthClient {
+            id: OAuthClientId(r.id),
+            client_id: r.client_id,
+            client_secret_hash: r.client_secret_hash,
+            name: r.name,
+            owner_id: UserId(r.owner_id),
+            redirect_uris: r.redirect_uris,
+            allowed_scopes: Scopes::from_bits(r.allowed_scopes as u64).unwrap_or(Scopes::empty()),
+            created: r.created,
+        }))
+    }
+
+    pub fn validates_redirect_uri(&self, redirect_uri: &str) -> bool {
+        self.redirect_uris.iter().any(|uri| uri == redirect_uri)
+    }
+}
@@ -0,0 +1,85 @@
+use super::ids::{OAuthClientId, UserId};
+use crate::database::models::DatabaseError;
+use crate::models::pats::Scopes;
+use chrono::{DateTime, Utc};
+use sha2::Digest;
+
+/// A short-lived authorization code minted by `/authorize` and redeemed
+/// exactly once by `/token`. Bound to the PKCE `code_challenge` the client
+/// presented up front, so the code is useless to anyone but the party
+/// holding the matching `code_verifier`.
+pub struct OAuthCode {
+    pub id: i64,
+    pub code_hash: String,
+    pub client_id: OAuthClientId,
+    pub user_id: UserId,
+    pub redirect_uri: String,
+    pub scopes: Scopes,
+    pub code_challenge: String,
+    pub created: DateTime<Utc>,
+    pub expires: DateTime<Utc>,
+}
+
+impl OAuthCode {
+    pub fn hash_code(code: &str) -> String {
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(code.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub async fn insert<'a, E>(&self, exec: E) -> Result<(), DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        sqlx::query!(
+            "
+            INSERT INTO oauth_codes (code_hash, client_id, user_id, redirect_uri, scopes, code_challenge, created, expires)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ",
+            self.code_hash,
+            self.client_id.0,
+            self.user_id.0,
+            self.redirect_uri,
+            self.scopes.bits() as i64,
+            self.code_challenge,
+            self.created,
+            self.expires,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Looks up and immediately deletes the code so it cannot be redeemed
+    /// twice, regardless of whether the lookup ultimately succeeds.
+    pub async fn take<'a, E>(code: &str, exec: E) -> Result<Option<OAuthCode>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let code_hash = Self::hash_code(code);
+
+        let row = sqlx::query!(
+            "
+            DELETE FROM oauth_codes
+            WHERE code_hash = $1
+            RETURNING id, code_hash, client_id, user_id, redirect_uri, scopes, code_challenge, created, expires
+            ",
+            code_hash
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|r| OAuthCode {
+            id: r.id,
+            code_hash: r.code_hash,
+            client_id: OAuthClientId(r.client_id),
+            user_id: UserId(r.user_id),
+            redirect_uri: r.redirect_uri,
+            scopes: Scopes::from_bits(r.scopes as u64).unwrap_or(Scopes::empty()),
+            code_challenge: r.code_challenge,
+            created: r.created,
+            expires: r.expires,
+        }))
+    }
+}
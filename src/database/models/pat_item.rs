@@ -0,0 +1,159 @@
+use super::ids::{OAuthClientId, PatId, UserId};
+use crate::database::models::DatabaseError;
+use crate::models::pats::Scopes;
+use chrono::{DateTime, Utc};
+use sha2::Digest;
+
+pub struct PersonalAccessToken {
+    pub id: PatId,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Scopes,
+    pub user_id: UserId,
+    /// Set when this token was minted by the `/token` endpoint of the
+    /// OAuth2 server on behalf of a third-party client, rather than
+    /// created directly by its owner.
+    pub oauth_client_id: Option<OAuthClientId>,
+    pub created: DateTime<Utc>,
+    pub expires: Option<DateTime<Utc>>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl PersonalAccessToken {
+    /// Personal access tokens are stored and looked up by the SHA-256 of
+    /// the plaintext token, never the token itself.
+    pub fn hash_token(token: &str) -> String {
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(token.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub async fn insert<'a, E>(&self, exec: E) -> Result<(), DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        sqlx::query!(
+            "
+            INSERT INTO pats (id, name, token_hash, scopes, user_id, oauth_client_id, created, expires, last_used)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ",
+            self.id.0,
+            self.name,
+            self.token_hash,
+            self.scopes.bits() as i64,
+            self.user_id.0,
+            self.oauth_client_id.map(|x| x.0),
+            self.created,
+            self.expires,
+            self.last_used,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_by_token<'a, E>(
+        token: &str,
+        exec: E,
+    ) -> Result<Option<PersonalAccessToken>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let token_hash = Self::hash_token(token);
+
+        let row = sqlx::query!(
+            "
+            SELECT id, name, token_hash, scopes, user_id, oauth_client_id, created, expires, last_used
+            FROM pats
+            WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|r| PersonalAccessToken {
+            id: PatId(r.id),
+            name: r.name,
+            token_hash: r.token_hash,
+            scopes: Scopes::from_bits(r.scopes as u64).unwrap_or(Scopes::empty()),
+            user_id: UserId(r.user_id),
+            oauth_client_id: r.oauth_client_id.map(OAuthClientId),
+            created: r.created,
+            expires: r.expires,
+            last_used: r.last_used,
+        }))
+    }
+
+    pub async fn get_user_pats<'a, E>(
+        user_id: UserId,
+        exec: E,
+    ) -> Result<Vec<PersonalAccessToken>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let rows = sqlx::query!(
+            "
+            SELECT id, name, token_hash, scopes, user_id, oauth_client_id, created, expires, last_used
+            FROM pats
+            WHERE user_id = $1
+            ORDER BY created DESC
+            ",
+            user_id.0
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| PersonalAccessToken {
+                id: PatId(r.id),
+                name: r.name,
+                token_hash: r.token_hash,
+                scopes: Scopes::from_bits(r.scopes as u64).unwrap_or(Scopes::empty()),
+                user_id: UserId(r.user_id),
+                oauth_client_id: r.oauth_client_id.map(OAuthClientId),
+                created: r.created,
+                expires: r.expires,
+                last_used: r.last_used,
+            })
+            .collect())
+    }
+
+    pub async fn remove<'a, E>(id: PatId, user_id: UserId, exec: E) -> Result<(), DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        sqlx::query!(
+            "
+            DELETE FROM pats
+            WHERE id = $1 AND user_id = $2
+            ",
+            id.0,
+            user_id.0
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn touch_last_used<'a, E>(id: PatId, exec: E) -> Result<(), DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        sqlx::query!(
+            "
+            UPDATE pats
+            SET last_used = NOW()
+            WHERE id = $1
+            ",
+            id.0
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+use super::ids::UserId;
+use crate::database::models::DatabaseError;
+use chrono::{DateTime, Utc};
+
+pub struct User {
+    pub id: UserId,
+    pub github_id: Option<i64>,
+    pub discord_id: Option<i64>,
+    pub google_id: Option<String>,
+    pub microsoft_id: Option<String>,
+    pub apple_id: Option<String>,
+    pub gitlab_id: Option<i64>,
+    pub username: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub created: DateTime<Utc>,
+    pub role: String,
+    pub badges: i64,
+    pub balance: rust_decimal::Decimal,
+    pub payout_wallet: Option<String>,
+    pub payout_wallet_type: Option<String>,
+    pub payout_address: Option<String>,
+}
+
+impl User {
+    pub async fn get_id<'a, E>(id: UserId, exec: E) -> Result<Option<User>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let row = sqlx::query!(
+            "
+            SELECT id, github_id, discord_id, google_id, microsoft_id, apple_id, gitlab_id,
+                   username, name, email, avatar_url, bio, created, role, badges,
+                   balance, payout_wallet, payout_wallet_type, payout_address
+            FROM users
+            WHERE id = $1
+            ",
+            id.0
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|r| User {
+            id: UserId(r.id),
+            github_id: r.github_id,
+            discord_id: r.discord_id,
+            google_id: r.google_id,
+            microsoft_id: r.microsoft_id,
+            apple_id: r.apple_id,
+            gitlab_id: r.gitlab_id,
+            username: r.username,
+            name: r.name,
+            email: r.email,
+            avatar_url: r.avatar_url,
+            bio: r.bio,
+            created: r.created,
+            role: r.role,
+            badges: r.badges,
+            balance: r.balance,
+            payout_wallet: r.payout_wallet,
+            payout_wallet_type: r.payout_wallet_type,
+            payout_address: r.payout_address,
+        }))
+    }
+
+    /// Looks up a user by the id their provider account `provider_id` is
+    /// linked to, if that provider has been linked to anyone yet.
+    pub async fn get_by_provider_id<'a, E>(
+        provider: &str,
+        provider_id: &str,
+        exec: E,
+    ) -> Result<Option<UserId>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let row = sqlx::query!(
+            "
+            SELECT id FROM users
+            WHERE (github_id::text = $1 AND $2 = 'github')
+               OR (discord_id::text = $1 AND $2 = 'discord')
+               OR (google_id = $1 AND $2 = 'google')
+               OR (microsoft_id = $1 AND $2 = 'microsoft')
+               OR (apple_id = $1 AND $2 = 'apple')
+               OR (gitlab_id::text = $1 AND $2 = 'gitlab')
+            ",
+            provider_id,
+            provider
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|r| UserId(r.id)))
+    }
+
+    pub async fn get_username<'a, E>(
+        username: &str,
+        exec: E,
+    ) -> Result<Option<UserId>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let row = sqlx::query!("SELECT id FROM users WHERE username = $1", username)
+            .fetch_optional(exec)
+            .await?;
+
+        Ok(row.map(|r| UserId(r.id)))
+    }
+
+    /// Binds a provider account to an existing user, so they can sign in
+    /// through it afterwards in addition to however they signed in now.
+    pub async fn link_provider<'a, E>(
+        user_id: UserId,
+        provider: &str,
+        provider_id: &str,
+        exec: E,
+    ) -> Result<(), DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        match provider {
+            "github" => {
+                sqlx::query!(
+                    "UPDATE users SET github_id = $1 WHERE id = $2",
+                    provider_id.parse::<i64>().ok(),
+                    user_id.0
+                )
+                .execute(exec)
+                .await?;
+            }
+            "discord" => {
+                sqlx::query!(
+                    "UPDATE users SET discord_id = $1 WHERE id = $2",
+                    provider_id.parse::<i64>().ok(),
+                    user_id.0
+                )
+                .execute(exec)
+                .await?;
+            }
+            "google" => {
+                sqlx::query!(
+                    "UPDATE users SET google_id = $1 WHERE id = $2",
+                    provider_id,
+                    user_id.0
+                )
+                .execute(exec)
+                .await?;
+            }
+            "microsoft" => {
+                sqlx::query!(
+                    "UPDATE users SET microsoft_id = $1 WHERE id = $2",
+                    provider_id,
+                    user_id.0
+                )
+                .execute(exec)
+                .await?;
+            }
+            "apple" => {
+                sqlx::query!(
+                    "UPDATE users SET apple_id = $1 WHERE id = $2",
+                    provider_id,
+                    user_id.0
+                )
+                .execute(exec)
+                .await?;
+            }
+            "gitlab" => {
+                sqlx::query!(
+                    "UPDATE users SET gitlab_id = $1 WHERE id = $2",
+                    provider_id.parse::<i64>().ok(),
+                    user_id.0
+                )
+                .execute(exec)
+                .await?;
+            }
+            // Validated against the known provider list by callers
+            // (see `provider_from_str`) before this is ever reached.
+            _ => unreachable!("unknown provider {provider}"),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use super::ids::UserId;
+use crate::database::models::DatabaseError;
+use chrono::{DateTime, Utc};
+
+pub struct WebAuthnCredential {
+    pub id: i64,
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub user_id: UserId,
+    pub created: DateTime<Utc>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl WebAuthnCredential {
+    pub async fn insert<'a, E>(&self, exec: E) -> Result<(), DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        sqlx::query!(
+            "
+            INSERT INTO webauthn_credentials (credential_id, public_key, sign_count, user_id, created, last_used)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ",
+            self.credential_id,
+            self.public_key,
+            self.sign_count,
+            self.user_id.0,
+            self.created,
+            self.last_used,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_by_credential_id<'a, E>(
+        credential_id: &[u8],
+        exec: E,
+    ) -> Result<Option<WebAuthnCredential>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let row = sqlx::query!(
+            "
+            SELECT id, credential_id, public_key, sign_count, user_id, created, last_used
+            FROM webauthn_credentials
+            WHERE credential_id = $1
+            ",
+            credential_id
+        )
+        .fetch_optional(exec)
+        .await?;
+
+        Ok(row.map(|r| WebAuthnCredential {
+            id: r.id,
+            credential_id: r.credential_id,
+            public_key: r.public_key,
+            sign_count: r.sign_count,
+            user_id: UserId(r.user_id),
+            created: r.created,
+            last_used: r.last_used,
+        }))
+    }
+
+    pub async fn get_user_credentials<'a, E>(
+        user_id: UserId,
+        exec: E,
+    ) -> Result<Vec<WebAuthnCredential>, DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        let rows = sqlx::query!(
+            "
+            SELECT id, credential_id, public_key, sign_count, user_id, created, last_used
+            FROM webauthn_credentials
+            WHERE user_id = $1
+            ",
+            user_id.0
+        )
+        .fetch_all(exec)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| WebAuthnCredential {
+                id: r.id,
+                credential_id: r.credential_id,
+                public_key: r.public_key,
+                sign_count: r.sign_count,
+                user_id: UserId(r.user_id),
+                created: r.created,
+                last_used: r.last_used,
+            })
+            .collect())
+    }
+
+    /// Persists both the new sign count and the re-serialized `public_key`
+    /// blob after a successful authentication ceremony. `webauthn-rs`'s
+    /// clone-detection reads the counter embedded in the deserialized
+    /// `Passkey`, not the `sign_count` column, so the column alone is not
+    /// enough — the caller must pass as `public_key` the credential's
+    /// `Passkey`, re-serialized after `update_credential` has applied the
+    /// `finish_passkey_authentication` result to it, or every later login
+    /// would still be checked against the original registration-time
+    /// counter.
+    pub async fn update_sign_count<'a, E>(
+        credential_id: &[u8],
+        sign_count: i64,
+        public_key: &[u8],
+        exec: E,
+    ) -> Result<(), DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+    {
+        sqlx::query!(
+            "
+            UPDATE webauthn_credentials
+            SET sign_count = $2, public_key = $3, last_used = NOW()
+            WHERE credential_id = $1
+            ",
+            credential_id,
+            sign_count,
+            public_key,
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+const BASE62_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+#[derive(thiserror::Error, Debug)]
+pub enum DecodingError {
+    #[error("Could not decode base62 id: {0}")]
+    Base62(String),
+}
+
+fn to_base62(mut num: u64) -> String {
+    if num == 0 {
+        return "0".to_string();
+    }
+
+    let mut encoded = Vec::new();
+    while num > 0 {
+        encoded.push(BASE62_CHARS[(num % 62) as usize]);
+        num /= 62;
+    }
+    encoded.reverse();
+
+    String::from_utf8(encoded).unwrap()
+}
+
+fn from_base62(string: &str) -> Result<u64, DecodingError> {
+    let mut num: u64 = 0;
+    for c in string.chars() {
+        let digit = BASE62_CHARS
+            .iter()
+            .position(|&x| x == c as u8)
+            .ok_or_else(|| DecodingError::Base62(string.to_string()))? as u64;
+        num = num
+            .checked_mul(62)
+            .and_then(|n| n.checked_add(digit))
+            .ok_or_else(|| DecodingError::Base62(string.to_string()))?;
+    }
+    Ok(num)
+}
+
+macro_rules! base62_id {
+    ($name:ident, $db_name:ident) => {
+        #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+        pub struct $name(pub u64);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", to_base62(self.0))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&to_base62(self.0))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                from_base62(&s).map($name).map_err(serde::de::Error::custom)
+            }
+        }
+
+        impl From<crate::database::models::ids::$db_name> for $name {
+            fn from(id: crate::database::models::ids::$db_name) -> Self {
+                $name(id.0 as u64)
+            }
+        }
+
+        impl From<$name> for crate::database::models::ids::$db_name {
+            fn from(id: $name) -> Self {
+                crate::database::models::ids::$db_name(id.0 as i64)
+            }
+        }
+    };
+}
+
+base62_id!(UserId, UserId);
+base62_id!(PatId, PatId);
+base62_id!(OAuthClientId, OAuthClientId);
@@ -0,0 +1,14 @@
+use super::ids::{OAuthClientId, UserId};
+use super::pats::Scopes;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct OAuthClient {
+    pub id: OAuthClientId,
+    pub name: String,
+    pub owner_id: UserId,
+    pub redirect_uris: Vec<String>,
+    pub allowed_scopes: Scopes,
+    /// Only ever returned once, at client creation time.
+    pub client_secret: Option<String>,
+}
@@ -0,0 +1,58 @@
+use super::ids::{PatId, UserId};
+use bitflags::bitflags;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+bitflags! {
+    /// The set of actions a personal access token (or any other scoped
+    /// token) is permitted to perform on behalf of its owner.
+    pub struct Scopes: u64 {
+        const PROJECT_READ = 1 << 0;
+        const PROJECT_WRITE = 1 << 1;
+        const VERSION_CREATE = 1 << 2;
+        const USER_READ = 1 << 3;
+        const NOTIFICATION_READ = 1 << 4;
+    }
+}
+
+impl Scopes {
+    /// The scope set implicitly granted to first-party session and OAuth
+    /// provider logins, which are not restricted to a subset of the API.
+    pub fn all_scopes() -> Self {
+        Self::all()
+    }
+}
+
+impl Serialize for Scopes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for Scopes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u64::deserialize(deserializer)?;
+        Scopes::from_bits(bits).ok_or_else(|| serde::de::Error::custom("invalid scope bitmask"))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PersonalAccessToken {
+    pub id: PatId,
+    pub name: String,
+    /// Only populated in the response to the creation request; the
+    /// plaintext token is never retrievable again afterwards.
+    pub access_token: Option<String>,
+    pub scopes: Scopes,
+    pub user_id: UserId,
+    pub created: DateTime<Utc>,
+    pub expires: Option<DateTime<Utc>>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct CreatePersonalAccessToken {
+    pub name: String,
+    pub scopes: Scopes,
+    pub expires: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,77 @@
+pub use super::ids::UserId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Role {
+    Developer,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub fn from_string(string: &str) -> Self {
+        match string {
+            "moderator" => Role::Moderator,
+            "admin" => Role::Admin,
+            _ => Role::Developer,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Developer => "developer",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn is_mod(&self) -> bool {
+        matches!(self, Role::Moderator | Role::Admin)
+    }
+
+    pub fn is_admin(&self) -> bool {
+        matches!(self, Role::Admin)
+    }
+}
+
+impl Serialize for Role {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Role {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Role::from_string(&s))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UserPayoutData {
+    pub balance: rust_decimal::Decimal,
+    pub payout_wallet: Option<String>,
+    pub payout_wallet_type: Option<String>,
+    pub payout_address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct User {
+    pub id: UserId,
+    pub github_id: Option<u64>,
+    pub discord_id: Option<u64>,
+    pub google_id: Option<String>,
+    pub microsoft_id: Option<String>,
+    pub apple_id: Option<String>,
+    pub gitlab_id: Option<u64>,
+    pub username: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub created: DateTime<Utc>,
+    pub role: Role,
+    pub badges: u64,
+    pub payout_data: Option<UserPayoutData>,
+}
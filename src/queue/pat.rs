@@ -0,0 +1,56 @@
+use crate::database::models::ids::PatId;
+use std::sync::Mutex;
+
+/// Batches personal-access-token `last_used` bumps the same way
+/// [`crate::queue::session::SessionQueue`] batches session touches, so a
+/// hot token doesn't cost a write on every single request.
+pub struct PatQueue {
+    queue: Mutex<Vec<PatId>>,
+}
+
+impl Default for PatQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PatQueue {
+    pub fn new() -> Self {
+        PatQueue {
+            queue: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub async fn add(&self, id: PatId) {
+        self.queue.lock().unwrap().push(id);
+    }
+
+    pub async fn index<'a, E>(&self, exec: E) -> Result<(), crate::database::models::DatabaseError>
+    where
+        E: sqlx::Executor<'a, Database = sqlx::Postgres> + Copy,
+    {
+        let ids = {
+            let mut queue = self.queue.lock().unwrap();
+            std::mem::take(&mut *queue)
+        };
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let raw_ids = ids.into_iter().map(|x| x.0).collect::<Vec<_>>();
+
+        sqlx::query!(
+            "
+            UPDATE pats
+            SET last_used = NOW()
+            WHERE id = ANY($1)
+            ",
+            &raw_ids
+        )
+        .execute(exec)
+        .await?;
+
+        Ok(())
+    }
+}
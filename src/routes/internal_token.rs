@@ -0,0 +1,152 @@
+use crate::database::models::oauth_client_item::OAuthClient;
+use crate::database::models::pat_item::PersonalAccessToken;
+use crate::database::models::session_item::Session;
+use crate::routes::ApiError;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use base64::Engine;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(introspect);
+    cfg.service(revoke);
+}
+
+/// Resource servers authenticate to introspection/revocation with their
+/// own OAuth client credentials, via HTTP Basic `client_id:client_secret`.
+/// This is a separate concern from whether the *token being introspected*
+/// is valid — that's reported in the response body, not as an error.
+async fn authenticate_client<'a, E>(req: &HttpRequest, exec: E) -> Result<OAuthClient, ApiError>
+where
+    E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+{
+    let header = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|x| x.to_str().ok())
+        .ok_or(ApiError::IntrospectionAuth)?;
+
+    let encoded = header.strip_prefix("Basic ").ok_or(ApiError::IntrospectionAuth)?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|_| ApiError::IntrospectionAuth)?;
+    let decoded = String::from_utf8(decoded).map_err(|_| ApiError::IntrospectionAuth)?;
+    let (client_id, client_secret) = decoded.split_once(':').ok_or(ApiError::IntrospectionAuth)?;
+
+    let client = OAuthClient::get_by_client_id(client_id, exec)
+        .await?
+        .ok_or(ApiError::IntrospectionAuth)?;
+
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(client_secret.as_bytes());
+    let secret_hash = format!("{:x}", hasher.finalize());
+
+    if client.client_secret_hash.as_deref() != Some(secret_hash.as_str()) {
+        return Err(ApiError::IntrospectionAuth);
+    }
+
+    Ok(client)
+}
+
+#[derive(Deserialize)]
+pub struct TokenBody {
+    pub token: String,
+}
+
+#[derive(Serialize)]
+pub struct IntrospectionResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+}
+
+impl IntrospectionResponse {
+    fn inactive() -> Self {
+        IntrospectionResponse {
+            active: false,
+            scope: None,
+            client_id: None,
+            sub: None,
+            exp: None,
+        }
+    }
+}
+
+/// Implements the RFC 7662 introspection contract: unknown or expired
+/// tokens are reported as `{"active": false}`, never as an error. Apart
+/// from database or cache failures, the only error case is the
+/// introspecting client itself failing to authenticate.
+#[post("introspect")]
+pub async fn introspect(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    body: web::Json<TokenBody>,
+) -> Result<HttpResponse, ApiError> {
+    authenticate_client(&req, &**pool).await?;
+
+    let response = match body.token.split_once('_') {
+        Some(("mrp", _)) => match PersonalAccessToken::get_by_token(&body.token, &**pool).await? {
+            Some(pat) if pat.expires.map(|e| e > Utc::now()).unwrap_or(true) => {
+                IntrospectionResponse {
+                    active: true,
+                    scope: Some(pat.scopes.bits()),
+                    client_id: pat
+                        .oauth_client_id
+                        .map(|x| crate::models::ids::OAuthClientId::from(x).to_string()),
+                    sub: Some(crate::models::ids::UserId::from(pat.user_id).to_string()),
+                    exp: pat.expires.map(|e| e.timestamp()),
+                }
+            }
+            _ => IntrospectionResponse::inactive(),
+        },
+        Some(("mra", _)) => match Session::get(&body.token, &**pool, &redis).await? {
+            Some(session) if session.expires > Utc::now() => IntrospectionResponse {
+                active: true,
+                scope: None,
+                client_id: None,
+                sub: Some(crate::models::ids::UserId::from(session.user_id).to_string()),
+                exp: Some(session.expires.timestamp()),
+            },
+            _ => IntrospectionResponse::inactive(),
+        },
+        _ => IntrospectionResponse::inactive(),
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Implements RFC 7009 revocation: invalidates the session or PAT
+/// immediately and purges any Redis cache entry for it. Revoking an
+/// already-invalid or unknown token is a no-op, per the RFC.
+#[post("revoke")]
+pub async fn revoke(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    body: web::Json<TokenBody>,
+) -> Result<HttpResponse, ApiError> {
+    authenticate_client(&req, &**pool).await?;
+
+    match body.token.split_once('_') {
+        Some(("mrp", _)) => {
+            if let Some(pat) = PersonalAccessToken::get_by_token(&body.token, &**pool).await? {
+                PersonalAccessToken::remove(pat.id, pat.user_id, &**pool).await?;
+            }
+        }
+        Some(("mra", _)) => {
+            Session::invalidate(&body.token, &**pool, &redis).await?;
+        }
+        _ => {}
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
@@ -7,6 +7,7 @@ pub mod v2;
 pub mod v3;
 
 mod index;
+mod internal_token;
 mod maven;
 mod not_found;
 mod updates;
@@ -14,9 +15,27 @@ mod updates;
 pub use self::not_found::not_found;
 
 pub fn root_config(cfg: &mut web::ServiceConfig) {
-    cfg.service(index::index_get);
-    cfg.service(web::scope("maven").configure(maven::config));
-    cfg.service(web::scope("updates").configure(updates::config));
+    cfg.service(
+        web::scope("")
+            .service(index::index_get)
+            .service(web::scope("maven").configure(maven::config))
+            .service(web::scope("updates").configure(updates::config))
+            .service(web::scope("_internal/token").configure(internal_token::config)),
+    );
+    // The CSRF middleware only needs to guard the cookie-authenticated,
+    // state-changing routes actually reachable from a browser session —
+    // that's the versioned API surfaces, not the unauthenticated/static
+    // routes mounted above.
+    cfg.service(
+        web::scope("v2")
+            .wrap(crate::util::csrf::Csrf)
+            .configure(v2::config),
+    );
+    cfg.service(
+        web::scope("v3")
+            .wrap(crate::util::csrf::Csrf)
+            .configure(v3::config),
+    );
     cfg.service(
         web::scope("api/v1").wrap_fn(|req, _srv| {
             async {
@@ -72,6 +91,12 @@ pub enum ApiError {
     PasswordHashing(#[from] argon2::password_hash::Error),
     #[error("Password strength checking error: {0}")]
     PasswordStrengthCheck(#[from] zxcvbn::ZxcvbnError),
+    #[error("Invalid client credentials for token introspection/revocation")]
+    IntrospectionAuth,
+    #[error("Missing or mismatched CSRF token")]
+    Csrf,
+    #[error("WebAuthn Error: {0}")]
+    WebAuthn(String),
 }
 
 impl actix_web::ResponseError for ApiError {
@@ -97,6 +122,9 @@ impl actix_web::ResponseError for ApiError {
             ApiError::ImageParse(..) => StatusCode::BAD_REQUEST,
             ApiError::PasswordHashing(..) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::PasswordStrengthCheck(..) => StatusCode::BAD_REQUEST,
+            ApiError::IntrospectionAuth => StatusCode::UNAUTHORIZED,
+            ApiError::Csrf => StatusCode::BAD_REQUEST,
+            ApiError::WebAuthn(..) => StatusCode::BAD_REQUEST,
         }
     }
 
@@ -123,6 +151,9 @@ impl actix_web::ResponseError for ApiError {
                 ApiError::ImageParse(..) => "invalid_image",
                 ApiError::PasswordHashing(..) => "password_hashing_error",
                 ApiError::PasswordStrengthCheck(..) => "strength_check_error",
+                ApiError::IntrospectionAuth => "introspection_auth_failed",
+                ApiError::Csrf => "csrf_error",
+                ApiError::WebAuthn(..) => "webauthn_error",
             },
             description: &self.to_string(),
         })
@@ -0,0 +1,189 @@
+use crate::auth::flows::AuthProvider;
+use crate::auth::session::get_session_metadata;
+use crate::auth::get_user_from_headers;
+use crate::database::models::user_item::User;
+use crate::queue::pat::PatQueue;
+use crate::queue::session::SessionQueue;
+use crate::routes::v3::require_full_auth;
+use crate::routes::ApiError;
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(provider_login);
+    cfg.service(provider_callback);
+    cfg.service(provider_link);
+}
+
+fn provider_from_str(provider: &str) -> Result<AuthProvider, ApiError> {
+    match provider {
+        "github" => Ok(AuthProvider::GitHub),
+        "discord" => Ok(AuthProvider::Discord),
+        "google" => Ok(AuthProvider::Google),
+        "microsoft" => Ok(AuthProvider::Microsoft),
+        "apple" => Ok(AuthProvider::Apple),
+        "gitlab" => Ok(AuthProvider::GitLab),
+        _ => Err(ApiError::InvalidInput(format!("unknown provider {provider}"))),
+    }
+}
+
+/// Redirects the browser to the given provider's consent screen. The
+/// logged-in user is not required here: this is the entry point for
+/// signing in with a provider account that is already linked to a user.
+///
+/// The `state` handed to the provider is also stashed in Redis so
+/// `provider_callback` can reject a callback that doesn't come back with
+/// a `state` we actually issued, which is what stops an attacker from
+/// fixating a victim's session via a callback URL built from the
+/// attacker's own authorization code.
+#[get("{provider}/login")]
+pub async fn provider_login(
+    redis: web::Data<deadpool_redis::Pool>,
+    info: web::Path<(String,)>,
+) -> Result<HttpResponse, ApiError> {
+    let provider = provider_from_str(&info.into_inner().0)?;
+
+    let state: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+
+    store_oauth_state(&redis, &state, provider).await?;
+
+    Ok(HttpResponse::Found()
+        .append_header(("Location", provider.authorize_url(&state)?))
+        .finish())
+}
+
+async fn store_oauth_state(
+    redis: &deadpool_redis::Pool,
+    state: &str,
+    provider: AuthProvider,
+) -> Result<(), ApiError> {
+    use deadpool_redis::redis::AsyncCommands;
+
+    let mut conn = redis
+        .get()
+        .await
+        .map_err(|e| ApiError::CustomAuthentication(e.to_string()))?;
+    conn.set_ex::<_, _, ()>(format!("oauth_state:{state}"), provider.as_str(), 600)
+        .await
+        .map_err(|e| ApiError::CustomAuthentication(e.to_string()))?;
+    Ok(())
+}
+
+/// Consumes the `state` stored by `provider_login`, failing unless it was
+/// issued for this exact provider and hasn't already been used.
+async fn take_oauth_state(
+    redis: &deadpool_redis::Pool,
+    state: &str,
+    provider: AuthProvider,
+) -> Result<(), ApiError> {
+    use deadpool_redis::redis::AsyncCommands;
+
+    let mut conn = redis
+        .get()
+        .await
+        .map_err(|e| ApiError::CustomAuthentication(e.to_string()))?;
+    let stored: String = conn
+        .get_del(format!("oauth_state:{state}"))
+        .await
+        .map_err(|_| ApiError::CustomAuthentication("invalid or expired login state".to_string()))?;
+
+    if stored == provider.as_str() {
+        Ok(())
+    } else {
+        Err(ApiError::CustomAuthentication(
+            "invalid or expired login state".to_string(),
+        ))
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct CallbackQuery {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct CallbackResponse {
+    pub session: String,
+}
+
+/// Receives the provider's redirect after the user approves the consent
+/// screen, exchanges `code` for a provider token, and resolves it to a
+/// `User` already linked to this provider account. On success this mints
+/// a normal `mra_` session, the same as the password login path does.
+///
+/// A provider account that has never been linked to any user can't sign
+/// in this way yet — the frontend should send such users through account
+/// creation and then `{provider}/link` instead.
+#[get("callback/{provider}")]
+pub async fn provider_callback(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    info: web::Path<(String,)>,
+    query: web::Query<CallbackQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let provider = provider_from_str(&info.into_inner().0)?;
+    take_oauth_state(&redis, &query.state, provider).await?;
+
+    let token = provider.get_token(&query.code).await?;
+    let auth_user = provider.get_user(&token).await?;
+
+    let user_id = User::get_by_provider_id(provider.as_str(), &auth_user.id, &**pool)
+        .await?
+        .ok_or_else(|| {
+            ApiError::CustomAuthentication(
+                "no account is linked to this provider; sign up first".to_string(),
+            )
+        })?;
+
+    let metadata = get_session_metadata(&req).await?;
+    let session =
+        crate::database::models::session_item::Session::issue(user_id, metadata, &**pool, &redis)
+            .await?;
+
+    Ok(HttpResponse::Ok().json(CallbackResponse {
+        session: session.session,
+    }))
+}
+
+/// Binds an additional provider account to the already-authenticated
+/// user, so they can sign in through either one afterwards.
+#[post("{provider}/link")]
+pub async fn provider_link(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    session_queue: web::Data<SessionQueue>,
+    pat_queue: web::Data<PatQueue>,
+    info: web::Path<(String,)>,
+    body: web::Json<LinkProviderCode>,
+) -> Result<HttpResponse, ApiError> {
+    let (user, scopes) =
+        get_user_from_headers(&req, &**pool, &redis, &session_queue, &pat_queue).await?;
+    require_full_auth(scopes)?;
+    let provider = provider_from_str(&info.into_inner().0)?;
+
+    let token = provider.get_token(&body.code).await?;
+    let auth_user = provider.get_user(&token).await?;
+
+    crate::database::models::user_item::User::link_provider(
+        user.id.into(),
+        provider.as_str(),
+        &auth_user.id,
+        &**pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(serde::Deserialize)]
+pub struct LinkProviderCode {
+    pub code: String,
+}
@@ -0,0 +1,32 @@
+use crate::models::pats::Scopes;
+use crate::routes::ApiError;
+use actix_web::web;
+
+pub mod auth;
+pub mod oauth;
+pub mod pats;
+pub mod webauthn;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("pat").configure(pats::config));
+    cfg.service(web::scope("auth").configure(auth::config));
+    cfg.service(web::scope("oauth").configure(oauth::config));
+    cfg.service(web::scope("auth/webauthn").configure(webauthn::config));
+}
+
+/// Gates an operation behind full account-level auth (an `mra_` session,
+/// a first-party provider login, or any token holding every scope) rather
+/// than accepting any narrowly-scoped token. Used for listing and revoking
+/// PATs, linking a new login provider, and registering a new passkey; the
+/// last two can go on to mint a full, unrestricted session independent of
+/// the original token's scope. PAT creation is not gated here: it instead
+/// caps the new token's scopes at those of the authenticating token.
+pub(crate) fn require_full_auth(scopes: Scopes) -> Result<(), ApiError> {
+    if scopes == Scopes::all_scopes() {
+        Ok(())
+    } else {
+        Err(ApiError::CustomAuthentication(
+            "this action requires a session or full-scoped token".to_string(),
+        ))
+    }
+}
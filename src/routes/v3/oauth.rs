@@ -0,0 +1,242 @@
+use crate::auth::get_user_from_headers;
+use crate::database::models::ids::generate_pat_id;
+use crate::database::models::oauth_client_item::OAuthClient;
+use crate::database::models::oauth_code_item::OAuthCode;
+use crate::database::models::pat_item::PersonalAccessToken;
+use crate::models::pats::Scopes;
+use crate::queue::pat::PatQueue;
+use crate::queue::session::SessionQueue;
+use crate::routes::ApiError;
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use subtle::ConstantTimeEq;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(authorize);
+    cfg.service(token);
+}
+
+fn parse_scope(scope: &str) -> Scopes {
+    scope
+        .split(' ')
+        .filter_map(|name| match name {
+            "PROJECT_READ" => Some(Scopes::PROJECT_READ),
+            "PROJECT_WRITE" => Some(Scopes::PROJECT_WRITE),
+            "VERSION_CREATE" => Some(Scopes::VERSION_CREATE),
+            "USER_READ" => Some(Scopes::USER_READ),
+            "NOTIFICATION_READ" => Some(Scopes::NOTIFICATION_READ),
+            _ => None,
+        })
+        .fold(Scopes::empty(), |acc, s| acc | s)
+}
+
+#[derive(Deserialize)]
+pub struct AuthorizeQuery {
+    pub response_type: String,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub state: String,
+    pub code_challenge: String,
+    pub code_challenge_method: String,
+}
+
+#[derive(Serialize)]
+pub struct AuthorizeResponse {
+    pub redirect_uri: String,
+}
+
+/// Authenticates the caller via `get_user_from_headers` and mints a
+/// short-lived authorization code bound to `code_challenge`. The code's
+/// scopes are the requested ones narrowed to what this client is allowed
+/// and what the caller's own token holds; requested scopes outside that
+/// set are dropped rather than rejected. The actual
+/// consent UI lives in the frontend; this just performs the grant.
+///
+/// This is a POST, not a GET, even though it's only ever read-driven from
+/// the consent screen: it mutates state (inserts the `oauth_codes` row),
+/// and a safe method would be exempt from the CSRF middleware, letting a
+/// third-party page trigger a grant via the user's cookie alone.
+#[post("authorize")]
+pub async fn authorize(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    session_queue: web::Data<SessionQueue>,
+    pat_queue: web::Data<PatQueue>,
+    body: web::Json<AuthorizeQuery>,
+) -> Result<HttpResponse, ApiError> {
+    if body.response_type != "code" {
+        return Err(ApiError::InvalidInput(
+            "only response_type=code is supported".to_string(),
+        ));
+    }
+    if body.code_challenge_method != "S256" {
+        return Err(ApiError::InvalidInput(
+            "only code_challenge_method=S256 is supported".to_string(),
+        ));
+    }
+
+    let (user, user_scopes) =
+        get_user_from_headers(&req, &**pool, &redis, &session_queue, &pat_queue).await?;
+
+    let client = OAuthClient::get_by_client_id(&body.client_id, &**pool)
+        .await?
+        .ok_or_else(|| ApiError::InvalidInput("unknown client_id".to_string()))?;
+
+    if !client.validates_redirect_uri(&body.redirect_uri) {
+        return Err(ApiError::InvalidInput("redirect_uri mismatch".to_string()));
+    }
+
+    let requested_scopes = parse_scope(&body.scope) & client.allowed_scopes & user_scopes;
+
+    let code = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect::<String>();
+
+    let oauth_code = OAuthCode {
+        id: 0,
+        code_hash: OAuthCode::hash_code(&code),
+        client_id: client.id,
+        user_id: user.id.into(),
+        redirect_uri: body.redirect_uri.clone(),
+        scopes: requested_scopes,
+        code_challenge: body.code_challenge.clone(),
+        created: chrono::Utc::now(),
+        expires: chrono::Utc::now() + chrono::Duration::minutes(10),
+    };
+
+    oauth_code.insert(&**pool).await?;
+
+    Ok(HttpResponse::Ok().json(AuthorizeResponse {
+        redirect_uri: format!(
+            "{}?code={}&state={}",
+            body.redirect_uri, code, body.state
+        ),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub code: String,
+    pub redirect_uri: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub code_verifier: String,
+}
+
+#[derive(Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub scope: u64,
+}
+
+/// Redeems an authorization code for an access token: the code is
+/// single-use (deleted on lookup) and the presented `code_verifier` must
+/// hash, via `BASE64URL(SHA256(code_verifier))`, to the `code_challenge`
+/// stashed at `/authorize` time.
+#[post("token")]
+pub async fn token(
+    pool: web::Data<sqlx::PgPool>,
+    body: web::Json<TokenRequest>,
+) -> Result<HttpResponse, ApiError> {
+    if body.grant_type != "authorization_code" {
+        return Err(ApiError::InvalidInput(
+            "only grant_type=authorization_code is supported".to_string(),
+        ));
+    }
+
+    let oauth_code = OAuthCode::take(&body.code, &**pool)
+        .await?
+        .ok_or_else(|| ApiError::CustomAuthentication("invalid or reused code".to_string()))?;
+
+    if oauth_code.expires < chrono::Utc::now() {
+        return Err(ApiError::CustomAuthentication(
+            "authorization code has expired".to_string(),
+        ));
+    }
+
+    if oauth_code.redirect_uri != body.redirect_uri {
+        return Err(ApiError::CustomAuthentication(
+            "redirect_uri mismatch".to_string(),
+        ));
+    }
+
+    let client = OAuthClient::get_by_client_id(&body.client_id, &**pool)
+        .await?
+        .ok_or_else(|| ApiError::CustomAuthentication("unknown client_id".to_string()))?;
+
+    if client.id != oauth_code.client_id {
+        return Err(ApiError::CustomAuthentication(
+            "client_id does not match the client the code was issued to".to_string(),
+        ));
+    }
+
+    // Confidential clients (those with a secret on file) must present it;
+    // public clients (mobile/SPA, no secret) rely on PKCE alone.
+    if let Some(expected_hash) = &client.client_secret_hash {
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(
+            body.client_secret
+                .as_deref()
+                .ok_or_else(|| ApiError::CustomAuthentication("client_secret required".to_string()))?
+                .as_bytes(),
+        );
+        let provided_hash = format!("{:x}", hasher.finalize());
+
+        if provided_hash.as_bytes().ct_eq(expected_hash.as_bytes()).unwrap_u8() == 0 {
+            return Err(ApiError::CustomAuthentication(
+                "invalid client_secret".to_string(),
+            ));
+        }
+    }
+
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(body.code_verifier.as_bytes());
+    let computed_challenge =
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(hasher.finalize());
+
+    if computed_challenge.as_bytes().ct_eq(oauth_code.code_challenge.as_bytes()).unwrap_u8() == 0
+    {
+        return Err(ApiError::CustomAuthentication(
+            "code_verifier does not match code_challenge".to_string(),
+        ));
+    }
+
+    let plaintext = format!(
+        "mrp_{}",
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect::<String>()
+    );
+
+    let pat = PersonalAccessToken {
+        id: generate_pat_id(&**pool).await?,
+        name: format!("oauth:{}", oauth_code.client_id.0),
+        token_hash: PersonalAccessToken::hash_token(&plaintext),
+        scopes: oauth_code.scopes,
+        user_id: oauth_code.user_id,
+        oauth_client_id: Some(oauth_code.client_id),
+        created: chrono::Utc::now(),
+        expires: Some(chrono::Utc::now() + chrono::Duration::days(90)),
+        last_used: None,
+    };
+
+    pat.insert(&**pool).await?;
+
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        access_token: plaintext,
+        token_type: "Bearer",
+        scope: pat.scopes.bits(),
+    }))
+}
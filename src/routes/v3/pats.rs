@@ -0,0 +1,124 @@
+use crate::auth::get_user_from_headers;
+use crate::database::models::ids::generate_pat_id;
+use crate::models::ids::PatId;
+use crate::database::models::pat_item::PersonalAccessToken as DBPersonalAccessToken;
+use crate::models::pats::{CreatePersonalAccessToken, PersonalAccessToken};
+use crate::queue::pat::PatQueue;
+use crate::queue::session::SessionQueue;
+use crate::routes::v3::require_full_auth;
+use crate::routes::ApiError;
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(pats_list);
+    cfg.service(pats_create);
+    cfg.service(pats_revoke);
+}
+
+#[get("")]
+pub async fn pats_list(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    session_queue: web::Data<SessionQueue>,
+    pat_queue: web::Data<PatQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (user, scopes) =
+        get_user_from_headers(&req, &**pool, &redis, &session_queue, &pat_queue).await?;
+    require_full_auth(scopes)?;
+
+    let pats = DBPersonalAccessToken::get_user_pats(user.id.into(), &**pool).await?;
+
+    let response = pats
+        .into_iter()
+        .map(|pat| PersonalAccessToken {
+            id: pat.id.into(),
+            name: pat.name,
+            access_token: None,
+            scopes: pat.scopes,
+            user_id: pat.user_id.into(),
+            created: pat.created,
+            expires: pat.expires,
+            last_used: pat.last_used,
+        })
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[post("")]
+pub async fn pats_create(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    session_queue: web::Data<SessionQueue>,
+    pat_queue: web::Data<PatQueue>,
+    body: web::Json<CreatePersonalAccessToken>,
+) -> Result<HttpResponse, ApiError> {
+    let (user, scopes) =
+        get_user_from_headers(&req, &**pool, &redis, &session_queue, &pat_queue).await?;
+
+    crate::routes::v3::webauthn::require_recent_assertion(user.id.into(), &**pool).await?;
+
+    // A personal access token can never be granted more than its creator
+    // is themselves allowed to do.
+    if !scopes.contains(body.scopes) {
+        return Err(ApiError::InvalidInput(
+            "requested scopes exceed the scopes of the authenticating token".to_string(),
+        ));
+    }
+
+    let plaintext = format!(
+        "mrp_{}",
+        rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect::<String>()
+    );
+
+    let pat = DBPersonalAccessToken {
+        id: generate_pat_id(&**pool).await?,
+        name: body.name.clone(),
+        token_hash: DBPersonalAccessToken::hash_token(&plaintext),
+        scopes: body.scopes,
+        user_id: user.id.into(),
+        oauth_client_id: None,
+        created: chrono::Utc::now(),
+        expires: body.expires,
+        last_used: None,
+    };
+
+    pat.insert(&**pool).await?;
+
+    Ok(HttpResponse::Ok().json(PersonalAccessToken {
+        id: pat.id.into(),
+        name: pat.name,
+        access_token: Some(plaintext),
+        scopes: pat.scopes,
+        user_id: pat.user_id.into(),
+        created: pat.created,
+        expires: pat.expires,
+        last_used: pat.last_used,
+    }))
+}
+
+#[delete("{id}")]
+pub async fn pats_revoke(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    session_queue: web::Data<SessionQueue>,
+    pat_queue: web::Data<PatQueue>,
+    info: web::Path<(PatId,)>,
+) -> Result<HttpResponse, ApiError> {
+    let (user, scopes) =
+        get_user_from_headers(&req, &**pool, &redis, &session_queue, &pat_queue).await?;
+    require_full_auth(scopes)?;
+
+    DBPersonalAccessToken::remove(info.into_inner().0.into(), user.id.into(), &**pool).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,261 @@
+use crate::auth::get_user_from_headers;
+use crate::auth::session::get_session_metadata;
+use crate::database::models::webauthn_item::WebAuthnCredential;
+use crate::queue::pat::PatQueue;
+use crate::queue::session::SessionQueue;
+use crate::routes::v3::require_full_auth;
+use crate::routes::ApiError;
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use chrono::Utc;
+use webauthn_rs::prelude::*;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::scope("register").service(register_begin).service(register_finish));
+    cfg.service(web::scope("authenticate").service(authenticate_begin).service(authenticate_finish));
+}
+
+/// Gates a sensitive operation (currently PAT creation) behind a
+/// webauthn assertion performed in the last few minutes, for users who
+/// have at least one passkey registered. Users without one are let
+/// through unchanged, since the gate can't apply to them.
+pub async fn require_recent_assertion<'a, E>(
+    user_id: crate::database::models::ids::UserId,
+    exec: E,
+) -> Result<(), ApiError>
+where
+    E: sqlx::Executor<'a, Database = sqlx::Postgres>,
+{
+    let credentials = WebAuthnCredential::get_user_credentials(user_id, exec).await?;
+
+    if credentials.is_empty() {
+        return Ok(());
+    }
+
+    let recently_used = credentials.iter().any(|c| {
+        c.last_used
+            .map(|used| Utc::now() - used < chrono::Duration::minutes(5))
+            .unwrap_or(false)
+    });
+
+    if recently_used {
+        Ok(())
+    } else {
+        Err(ApiError::WebAuthn(
+            "this action requires a recent passkey confirmation".to_string(),
+        ))
+    }
+}
+
+fn webauthn() -> Result<Webauthn, ApiError> {
+    let rp_id = dotenvy::var("WEBAUTHN_RP_ID").map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+    let rp_origin_str =
+        dotenvy::var("WEBAUTHN_RP_ORIGIN").map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+    let rp_origin = Url::parse(&rp_origin_str).map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+
+    WebauthnBuilder::new(&rp_id, &rp_origin)
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?
+        .rp_name("Modrinth")
+        .build()
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))
+}
+
+async fn store_challenge_state<T: serde::Serialize>(
+    redis: &deadpool_redis::Pool,
+    key: &str,
+    state: &T,
+) -> Result<(), ApiError> {
+    use deadpool_redis::redis::AsyncCommands;
+
+    let mut conn = redis
+        .get()
+        .await
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+    let serialized = serde_json::to_string(state).map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+    conn.set_ex::<_, _, ()>(key, serialized, 300)
+        .await
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+    Ok(())
+}
+
+async fn take_challenge_state<T: serde::de::DeserializeOwned>(
+    redis: &deadpool_redis::Pool,
+    key: &str,
+) -> Result<T, ApiError> {
+    use deadpool_redis::redis::AsyncCommands;
+
+    let mut conn = redis
+        .get()
+        .await
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+    let serialized: String = conn
+        .get_del(key)
+        .await
+        .map_err(|_| ApiError::WebAuthn("no pending webauthn ceremony for this user".to_string()))?;
+    serde_json::from_str(&serialized).map_err(|e| ApiError::WebAuthn(e.to_string()))
+}
+
+/// Starts passkey registration for the already-logged-in user.
+#[post("begin")]
+pub async fn register_begin(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    session_queue: web::Data<SessionQueue>,
+    pat_queue: web::Data<PatQueue>,
+) -> Result<HttpResponse, ApiError> {
+    let (user, scopes) =
+        get_user_from_headers(&req, &**pool, &redis, &session_queue, &pat_queue).await?;
+    require_full_auth(scopes)?;
+
+    let existing = WebAuthnCredential::get_user_credentials(user.id.into(), &**pool).await?;
+    let exclude_credentials = existing
+        .iter()
+        .filter_map(|c| CredentialID::try_from(c.credential_id.as_slice()).ok())
+        .collect::<Vec<_>>();
+
+    let (ccr, reg_state) = webauthn()?
+        .start_passkey_registration(
+            Uuid::new_v4(),
+            &user.username,
+            &user.username,
+            Some(exclude_credentials),
+        )
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+
+    store_challenge_state(&redis, &format!("webauthn_reg:{}", user.id), &reg_state).await?;
+
+    Ok(HttpResponse::Ok().json(ccr))
+}
+
+/// Completes passkey registration and stores the new credential.
+#[post("finish")]
+pub async fn register_finish(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    session_queue: web::Data<SessionQueue>,
+    pat_queue: web::Data<PatQueue>,
+    body: web::Json<RegisterPublicKeyCredential>,
+) -> Result<HttpResponse, ApiError> {
+    let (user, scopes) =
+        get_user_from_headers(&req, &**pool, &redis, &session_queue, &pat_queue).await?;
+    require_full_auth(scopes)?;
+
+    let reg_state: PasskeyRegistration =
+        take_challenge_state(&redis, &format!("webauthn_reg:{}", user.id)).await?;
+
+    let passkey = webauthn()?
+        .finish_passkey_registration(&body, &reg_state)
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+
+    let credential = WebAuthnCredential {
+        id: 0,
+        credential_id: passkey.cred_id().as_ref().to_vec(),
+        public_key: serde_json::to_vec(&passkey).map_err(|e| ApiError::WebAuthn(e.to_string()))?,
+        sign_count: 0,
+        user_id: user.id.into(),
+        created: Utc::now(),
+        last_used: None,
+    };
+
+    credential.insert(&**pool).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(serde::Deserialize)]
+pub struct AuthenticateBeginBody {
+    pub username: String,
+}
+
+/// Starts passkey assertion for a not-yet-authenticated user, identified
+/// by username. No session is required for this step.
+#[post("begin")]
+pub async fn authenticate_begin(
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    body: web::Json<AuthenticateBeginBody>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = crate::database::models::user_item::User::get_username(&body.username, &**pool)
+        .await?
+        .ok_or_else(|| ApiError::WebAuthn("unknown user".to_string()))?;
+
+    let credentials = WebAuthnCredential::get_user_credentials(user_id, &**pool).await?;
+    let passkeys = credentials
+        .iter()
+        .filter_map(|c| serde_json::from_slice::<Passkey>(&c.public_key).ok())
+        .collect::<Vec<_>>();
+
+    if passkeys.is_empty() {
+        return Err(ApiError::WebAuthn(
+            "user has no registered passkeys".to_string(),
+        ));
+    }
+
+    let (rcr, auth_state) = webauthn()?
+        .start_passkey_authentication(&passkeys)
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+
+    store_challenge_state(
+        &redis,
+        &format!("webauthn_auth:{}", crate::models::ids::UserId::from(user_id)),
+        &auth_state,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "user_id": crate::models::ids::UserId::from(user_id),
+        "challenge": rcr,
+    })))
+}
+
+#[derive(serde::Deserialize)]
+pub struct AuthenticateFinishBody {
+    pub user_id: crate::models::ids::UserId,
+    pub credential: PublicKeyCredential,
+}
+
+/// Completes passkey assertion and, on success, mints a normal `mra_`
+/// session exactly as the password login path does.
+#[post("finish")]
+pub async fn authenticate_finish(
+    req: HttpRequest,
+    pool: web::Data<sqlx::PgPool>,
+    redis: web::Data<deadpool_redis::Pool>,
+    body: web::Json<AuthenticateFinishBody>,
+) -> Result<HttpResponse, ApiError> {
+    let db_user_id: crate::database::models::ids::UserId = body.user_id.into();
+
+    let auth_state: PasskeyAuthentication =
+        take_challenge_state(&redis, &format!("webauthn_auth:{}", body.user_id)).await?;
+
+    let result = webauthn()?
+        .finish_passkey_authentication(&body.credential, &auth_state)
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+
+    let credential = WebAuthnCredential::get_by_credential_id(result.cred_id().as_ref(), &**pool)
+        .await?
+        .ok_or_else(|| ApiError::WebAuthn("unknown credential".to_string()))?;
+    let mut passkey: Passkey = serde_json::from_slice(&credential.public_key)
+        .map_err(|e| ApiError::WebAuthn(e.to_string()))?;
+    passkey.update_credential(&result);
+
+    WebAuthnCredential::update_sign_count(
+        result.cred_id().as_ref(),
+        result.counter() as i64,
+        &serde_json::to_vec(&passkey).map_err(|e| ApiError::WebAuthn(e.to_string()))?,
+        &**pool,
+    )
+    .await?;
+
+    let metadata = get_session_metadata(&req).await?;
+    let session = crate::database::models::session_item::Session::issue(
+        db_user_id,
+        metadata,
+        &**pool,
+        &redis,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "session": session.session })))
+}
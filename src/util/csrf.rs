@@ -0,0 +1,157 @@
+use actix_web::body::EitherBody;
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header::AUTHORIZATION, Method};
+use actix_web::{Error, ResponseError};
+use crate::routes::ApiError;
+use futures::future::LocalBoxFuture;
+use hmac::{Hmac, Mac};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use sha2::Sha256;
+use std::future::{ready, Ready};
+use subtle::ConstantTimeEq;
+
+const CSRF_COOKIE: &str = "csrf_token";
+const CSRF_HEADER: &str = "X-CSRF-Token";
+const SESSION_COOKIE: &str = "session";
+
+/// Signs `nonce` with the `CSRF_SECRET` server secret, returning
+/// `nonce.signature`. Without this, a cookie-tossing attacker on a
+/// sibling subdomain could set their own `csrf_token` cookie to a value
+/// of their choosing and then simply echo it back in the header,
+/// defeating a plain double-submit check. Signing means only a value
+/// this server minted can ever validate.
+fn sign_token(nonce: &str) -> Result<String, ApiError> {
+    let secret = dotenvy::var("CSRF_SECRET")?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(nonce.as_bytes());
+    let signature = hex::encode(mac.finalize().into_bytes());
+    Ok(format!("{nonce}.{signature}"))
+}
+
+fn verify_token(token: &str) -> bool {
+    let Some((nonce, signature)) = token.split_once('.') else {
+        return false;
+    };
+
+    let Ok(expected) = sign_token(nonce) else {
+        return false;
+    };
+    let Some((_, expected_signature)) = expected.split_once('.') else {
+        return false;
+    };
+
+    signature.as_bytes().ct_eq(expected_signature.as_bytes()).unwrap_u8() == 1
+}
+
+/// Double-submit-cookie CSRF protection for cookie-authenticated,
+/// state-changing requests.
+///
+/// Safe requests (GET/HEAD/OPTIONS) are given a random token in a cookie
+/// if they don't already have one. State-changing requests made with a
+/// session cookie must echo that same value back in the `X-CSRF-Token`
+/// header, or they're rejected — this is impossible for a third-party
+/// site to forge, since it can't read the cookie it would need to echo.
+/// Bearer-token (non-cookie) requests are exempt, since CSRF only affects
+/// credentials the browser attaches automatically.
+#[derive(Default)]
+pub struct Csrf;
+
+impl<S, B> Transform<S, ServiceRequest> for Csrf
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CsrfMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CsrfMiddleware { service }))
+    }
+}
+
+pub struct CsrfMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for CsrfMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let is_bearer_request = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|x| x.to_str().ok())
+            .map(|x| !x.is_empty())
+            .unwrap_or(false);
+
+        let is_safe_method = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+        if !is_bearer_request && !is_safe_method {
+            let cookie_value = req.cookie(CSRF_COOKIE).map(|c| c.value().to_string());
+            let header_value = req
+                .headers()
+                .get(CSRF_HEADER)
+                .and_then(|x| x.to_str().ok())
+                .map(str::to_string);
+            let has_session_cookie = req.cookie(SESSION_COOKIE).is_some();
+
+            if has_session_cookie {
+                let valid = match (cookie_value, header_value) {
+                    (Some(cookie), Some(header)) => {
+                        verify_token(&cookie)
+                            && cookie.as_bytes().ct_eq(header.as_bytes()).unwrap_u8() == 1
+                    }
+                    _ => false,
+                };
+
+                if !valid {
+                    let (http_req, _) = req.into_parts();
+                    let response = crate::routes::ApiError::Csrf
+                        .error_response()
+                        .map_into_right_body();
+                    return Box::pin(async move { Ok(ServiceResponse::new(http_req, response)) });
+                }
+            }
+        }
+
+        let needs_cookie = is_safe_method && req.cookie(CSRF_COOKIE).is_none();
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            let mut res = res.map_into_left_body();
+
+            if needs_cookie {
+                let nonce: String = rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(32)
+                    .map(char::from)
+                    .collect();
+                let token = sign_token(&nonce)?;
+
+                let cookie = Cookie::build(CSRF_COOKIE, token)
+                    .path("/")
+                    .same_site(SameSite::Strict)
+                    .finish();
+
+                let _ = res.response_mut().add_cookie(&cookie);
+            }
+
+            Ok(res)
+        })
+    }
+}